@@ -1,6 +1,7 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use tauri::Emitter;
 use tauri::Manager;
 use tauri::WebviewWindowBuilder;
 use tauri::WebviewUrl;
@@ -12,6 +13,7 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
 use std::sync::atomic::AtomicU32;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppSettings {
@@ -19,6 +21,18 @@ pub struct AppSettings {
     pub window_width: u32,
     pub window_height: u32,
     pub fullscreen: bool,
+    // Seconds of inactivity before the screen locks, 0 disables the lock
+    #[serde(default)]
+    pub idle_lock_secs: u64,
+    // Step shelf prices are rounded to for display (e.g. 0.05), 0 disables rounding
+    #[serde(default)]
+    pub display_rounding: f64,
+    #[serde(default = "default_ui_zoom")]
+    pub ui_zoom: f64,
+}
+
+fn default_ui_zoom() -> f64 {
+    1.0
 }
 
 impl Default for AppSettings {
@@ -28,13 +42,80 @@ impl Default for AppSettings {
             window_width: 1400,
             window_height: 900,
             fullscreen: false,
+            idle_lock_secs: 0,
+            display_rounding: 0.0,
+            ui_zoom: default_ui_zoom(),
         }
     }
 }
 
+// Persisted across launches to detect crashes and power loss
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionState {
+    pub running: bool,
+    pub last_clean_shutdown: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ShutdownStatus {
+    pub previous_clean: bool,
+    pub last_clean_shutdown: Option<u64>,
+}
+
+// One changed field in a settings save, appended to settings_audit.jsonl
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SettingsAuditEntry {
+    pub timestamp: u64,
+    pub field: String,
+    pub old_value: serde_json::Value,
+    pub new_value: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConnectivityStatus {
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub status: Option<u16>,
+}
+
 struct AppState {
     settings: Mutex<AppSettings>,
     settings_path: PathBuf,
+    settings_audit_path: PathBuf,
+    idle_timer: Mutex<IdleTimer>,
+    session_path: PathBuf,
+    previous_session: SessionState,
+    // Result of the last reachability check, None until the first one runs
+    last_reachable: Mutex<Option<bool>>,
+}
+
+// Tracks user activity so an idle terminal can be locked
+struct IdleTimer {
+    last_activity: Instant,
+    fired: bool,
+}
+
+impl IdleTimer {
+    fn new(now: Instant) -> Self {
+        Self { last_activity: now, fired: false }
+    }
+
+    fn reset(&mut self, now: Instant) {
+        self.last_activity = now;
+        self.fired = false;
+    }
+
+    // Returns true once per idle period when the threshold has passed
+    fn check(&mut self, now: Instant, threshold: Duration) -> bool {
+        if self.fired || threshold.is_zero() {
+            return false;
+        }
+        if now.duration_since(self.last_activity) >= threshold {
+            self.fired = true;
+            return true;
+        }
+        false
+    }
 }
 
 // Counter for unique popup window labels
@@ -46,10 +127,132 @@ fn get_settings_path(app: &tauri::App) -> PathBuf {
     app_data_dir.join("settings.json")
 }
 
+fn get_session_path(app: &tauri::App) -> PathBuf {
+    let app_data_dir = app.path().app_data_dir().expect("Failed to get app data dir");
+    fs::create_dir_all(&app_data_dir).ok();
+    app_data_dir.join("session.json")
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Write to a sibling temp file and rename it over the target, so a crash
+// mid-write leaves either the old or the new file, never a truncated one
+fn write_file_atomic(path: &PathBuf, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = fs::File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+fn load_session(path: &PathBuf) -> SessionState {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_session(path: &PathBuf, session: &SessionState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(session)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+    write_file_atomic(path, &content)
+        .map_err(|e| format!("Failed to write session: {}", e))?;
+    Ok(())
+}
+
+fn save_clean_shutdown(path: &PathBuf, timestamp: u64) -> Result<(), String> {
+    save_session(path, &SessionState {
+        running: false,
+        last_clean_shutdown: Some(timestamp),
+    })
+}
+
+fn shutdown_status(previous_session: &SessionState) -> ShutdownStatus {
+    ShutdownStatus {
+        previous_clean: !previous_session.running,
+        last_clean_shutdown: previous_session.last_clean_shutdown,
+    }
+}
+
+// Record that this session ended gracefully
+fn mark_clean_shutdown(app: &tauri::AppHandle) {
+    let state: tauri::State<AppState> = app.state();
+    if let Err(e) = save_clean_shutdown(&state.session_path, unix_timestamp()) {
+        eprintln!("{}", e);
+    }
+}
+
+// Overlay each stored field onto the defaults, keeping only the fields that still
+// deserialize, so a missing or mistyped field doesn't discard the others
+fn merge_settings(stored: &serde_json::Value) -> AppSettings {
+    let defaults = AppSettings::default();
+    let (Ok(serde_json::Value::Object(mut merged)), serde_json::Value::Object(stored)) =
+        (serde_json::to_value(&defaults), stored)
+    else {
+        return defaults;
+    };
+
+    for (field, value) in stored {
+        if !merged.contains_key(field) {
+            continue;
+        }
+        let mut candidate = merged.clone();
+        candidate.insert(field.clone(), value.clone());
+        if serde_json::from_value::<AppSettings>(serde_json::Value::Object(candidate.clone())).is_ok() {
+            merged = candidate;
+        }
+    }
+
+    serde_json::from_value(serde_json::Value::Object(merged)).unwrap_or(defaults)
+}
+
+// Write fields added since the file was created back into it. Keys this
+// version doesn't know (e.g. from a newer build) and values that failed to
+// parse are left exactly as stored.
+fn add_missing_settings_fields(path: &PathBuf, stored: serde_json::Value, settings: &AppSettings) {
+    let (serde_json::Value::Object(mut stored), Ok(serde_json::Value::Object(current))) =
+        (stored, serde_json::to_value(settings))
+    else {
+        return;
+    };
+
+    let mut changed = false;
+    for (field, value) in current {
+        if !stored.contains_key(&field) {
+            stored.insert(field, value);
+            changed = true;
+        }
+    }
+    if !changed {
+        return;
+    }
+
+    if let Ok(content) = serde_json::to_string_pretty(&serde_json::Value::Object(stored)) {
+        let _ = write_file_atomic(path, &content);
+    }
+}
+
 fn load_settings(path: &PathBuf) -> AppSettings {
     if path.exists() {
         if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(settings) = serde_json::from_str(&content) {
+            if let Ok(stored) = serde_json::from_str::<serde_json::Value>(&content) {
+                let settings = merge_settings(&stored);
+                add_missing_settings_fields(path, stored, &settings);
                 return settings;
             }
         }
@@ -60,25 +263,143 @@ fn load_settings(path: &PathBuf) -> AppSettings {
 fn save_settings_to_file(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
     let content = serde_json::to_string_pretty(settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(path, content)
+    write_file_atomic(path, &content)
         .map_err(|e| format!("Failed to write settings: {}", e))?;
     Ok(())
 }
 
+// Settings whose values must never be written to the audit log
+fn is_sensitive_setting(field: &str) -> bool {
+    field
+        .split('_')
+        .any(|part| matches!(part, "pin" | "password" | "secret" | "token"))
+}
+
+fn diff_settings(old: &AppSettings, new: &AppSettings) -> Vec<SettingsAuditEntry> {
+    match (serde_json::to_value(old), serde_json::to_value(new)) {
+        (Ok(old), Ok(new)) => diff_settings_values(&old, &new, unix_timestamp()),
+        _ => Vec::new(),
+    }
+}
+
+fn diff_settings_values(
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    timestamp: u64,
+) -> Vec<SettingsAuditEntry> {
+    let (serde_json::Value::Object(old), serde_json::Value::Object(new)) = (old, new) else {
+        return Vec::new();
+    };
+
+    new.iter()
+        .filter(|(field, value)| old.get(*field) != Some(*value))
+        .map(|(field, value)| {
+            let redact = |v: serde_json::Value| {
+                if is_sensitive_setting(field) {
+                    serde_json::Value::String("[redacted]".to_string())
+                } else {
+                    v
+                }
+            };
+            SettingsAuditEntry {
+                timestamp,
+                field: field.clone(),
+                old_value: redact(old.get(field).cloned().unwrap_or(serde_json::Value::Null)),
+                new_value: redact(value.clone()),
+            }
+        })
+        .collect()
+}
+
+fn record_settings_audit(path: &PathBuf, old: &AppSettings, new: &AppSettings) {
+    use std::io::Write;
+    let entries = diff_settings(old, new);
+    if entries.is_empty() {
+        return;
+    }
+    let result = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| {
+            for entry in &entries {
+                let line = serde_json::to_string(entry).unwrap_or_default();
+                writeln!(file, "{}", line)?;
+            }
+            Ok(())
+        });
+    if let Err(e) = result {
+        eprintln!("Failed to write settings audit: {}", e);
+    }
+}
+
+#[tauri::command]
+fn get_settings_audit(
+    state: tauri::State<AppState>,
+    limit: u32,
+) -> Result<Vec<SettingsAuditEntry>, String> {
+    let content = match fs::read_to_string(&state.settings_audit_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read settings audit: {}", e)),
+    };
+
+    // Newest first
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit as usize)
+        .collect())
+}
+
 #[tauri::command]
 fn get_settings(state: tauri::State<AppState>) -> Result<AppSettings, String> {
     let settings = state.settings.lock().map_err(|e| e.to_string())?;
     Ok(settings.clone())
 }
 
+// Require an http(s) URL with a host. Directory-like paths get a trailing slash;
+// a last segment with an extension (e.g. index.html) is treated as a file and kept
+fn normalize_server_url(input: &str) -> Result<String, String> {
+    let mut parsed = url::Url::parse(input.trim())
+        .map_err(|e| format!("Invalid server URL {:?}: {}", input, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "Server URL must start with http:// or https://, got {}://",
+            parsed.scheme()
+        ));
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(format!("Server URL {:?} has no host", input));
+    }
+    let last_segment = parsed.path().rsplit('/').next().unwrap_or("");
+    if !last_segment.is_empty() && !last_segment.contains('.') {
+        let path = format!("{}/", parsed.path());
+        parsed.set_path(&path);
+    }
+    Ok(parsed.to_string())
+}
+
 #[tauri::command]
 fn save_settings(
+    app: tauri::AppHandle,
     state: tauri::State<AppState>,
-    new_settings: AppSettings,
+    mut new_settings: AppSettings,
 ) -> Result<(), String> {
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    *settings = new_settings.clone();
-    save_settings_to_file(&state.settings_path, &new_settings)?;
+    new_settings.server_url = normalize_server_url(&new_settings.server_url)?;
+    new_settings.ui_zoom = clamp_ui_zoom(new_settings.ui_zoom);
+    {
+        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let old_settings = settings.clone();
+        *settings = new_settings.clone();
+        save_settings_to_file(&state.settings_path, &new_settings)?;
+        record_settings_audit(&state.settings_audit_path, &old_settings, &new_settings);
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        window.set_zoom(new_settings.ui_zoom).map_err(|e| e.to_string())?;
+    }
     Ok(())
 }
 
@@ -90,12 +411,309 @@ fn get_server_url(state: tauri::State<AppState>) -> Result<String, String> {
 
 #[tauri::command]
 fn set_server_url(state: tauri::State<AppState>, url: String) -> Result<(), String> {
+    let url = normalize_server_url(&url)?;
     let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    let old_settings = settings.clone();
     settings.server_url = url;
     save_settings_to_file(&state.settings_path, &settings)?;
+    record_settings_audit(&state.settings_audit_path, &old_settings, &settings);
+    Ok(())
+}
+
+fn clamp_ui_zoom(zoom: f64) -> f64 {
+    if zoom.is_finite() {
+        zoom.clamp(0.5, 3.0)
+    } else {
+        default_ui_zoom()
+    }
+}
+
+#[tauri::command]
+fn set_ui_zoom(
+    app: tauri::AppHandle,
+    state: tauri::State<AppState>,
+    zoom: f64,
+) -> Result<f64, String> {
+    let zoom = clamp_ui_zoom(zoom);
+    {
+        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let old_settings = settings.clone();
+        settings.ui_zoom = zoom;
+        save_settings_to_file(&state.settings_path, &settings)?;
+        record_settings_audit(&state.settings_audit_path, &old_settings, &settings);
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        window.set_zoom(zoom).map_err(|e| e.to_string())?;
+    }
+    Ok(zoom)
+}
+
+// HEAD the saved server URL and return the HTTP status. Only the configured
+// server is reachable through this, so pages can't use it to probe other hosts.
+#[tauri::command]
+async fn ping_server(state: tauri::State<'_, AppState>) -> Result<u16, String> {
+    let url = state.settings.lock().map_err(|e| e.to_string())?.server_url.clone();
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .head(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Server not reachable: {}", e))?;
+    Ok(response.status().as_u16())
+}
+
+const DEFAULT_CONNECTIVITY_TIMEOUT_MS: u64 = 3000;
+// Anything shorter fails even against a healthy server on the LAN
+const MIN_CONNECTIVITY_TIMEOUT_MS: u64 = 250;
+// How often the background check runs so the UI never has to poll
+const CONNECTIVITY_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+fn connectivity_timeout(timeout_ms: Option<u64>) -> Result<Duration, String> {
+    let timeout_ms = timeout_ms.unwrap_or(DEFAULT_CONNECTIVITY_TIMEOUT_MS);
+    if timeout_ms < MIN_CONNECTIVITY_TIMEOUT_MS {
+        return Err(format!(
+            "Timeout must be at least {} ms, got {} ms",
+            MIN_CONNECTIVITY_TIMEOUT_MS, timeout_ms
+        ));
+    }
+    Ok(Duration::from_millis(timeout_ms))
+}
+
+// GET the URL; any HTTP response, whatever its status, counts as reachable
+async fn check_connectivity(url: &str, timeout: Duration) -> ConnectivityStatus {
+    let unreachable = ConnectivityStatus {
+        reachable: false,
+        latency_ms: None,
+        status: None,
+    };
+    let Ok(client) = reqwest::Client::builder().timeout(timeout).build() else {
+        return unreachable;
+    };
+
+    let started = Instant::now();
+    match client.get(url).send().await {
+        Ok(response) => ConnectivityStatus {
+            reachable: true,
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            status: Some(response.status().as_u16()),
+        },
+        Err(_) => unreachable,
+    }
+}
+
+// Store the latest result and report whether reachability flipped (or is first known)
+fn update_last_reachable(last_reachable: &Mutex<Option<bool>>, reachable: bool) -> Result<bool, String> {
+    let mut last_reachable = last_reachable.lock().map_err(|e| e.to_string())?;
+    let changed = *last_reachable != Some(reachable);
+    *last_reachable = Some(reachable);
+    Ok(changed)
+}
+
+// Check the saved server URL and emit connectivity-changed on a flip
+async fn refresh_connectivity(
+    app: &tauri::AppHandle,
+    timeout: Duration,
+) -> Result<ConnectivityStatus, String> {
+    let url = {
+        let state: tauri::State<AppState> = app.state();
+        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        settings.server_url.clone()
+    };
+
+    let connectivity = check_connectivity(&url, timeout).await;
+
+    let state: tauri::State<AppState> = app.state();
+    if update_last_reachable(&state.last_reachable, connectivity.reachable)? {
+        let _ = app.emit("connectivity-changed", &connectivity);
+    }
+    Ok(connectivity)
+}
+
+// On-demand check; the same check also runs in the background from setup
+#[tauri::command]
+async fn is_server_reachable(
+    app: tauri::AppHandle,
+    timeout_ms: Option<u64>,
+) -> Result<ConnectivityStatus, String> {
+    let timeout = connectivity_timeout(timeout_ms)?;
+    refresh_connectivity(&app, timeout).await
+}
+
+#[tauri::command]
+fn get_shutdown_status(state: tauri::State<AppState>) -> ShutdownStatus {
+    shutdown_status(&state.previous_session)
+}
+
+#[tauri::command]
+fn report_activity(state: tauri::State<AppState>) -> Result<(), String> {
+    let mut idle_timer = state.idle_timer.lock().map_err(|e| e.to_string())?;
+    idle_timer.reset(Instant::now());
     Ok(())
 }
 
+// Sum a drawer count given as (denomination, quantity) pairs, in integer cents
+#[tauri::command]
+fn compute_cash_count(counts: Vec<(f64, u32)>) -> Result<f64, String> {
+    let mut total_cents: i64 = 0;
+    for (denomination, quantity) in counts {
+        if !denomination.is_finite() || denomination <= 0.0 {
+            return Err(format!("Invalid denomination: {}", denomination));
+        }
+        // Sub-cent coins (e.g. 5 fils) cannot be counted in cents without losing value
+        let cents = (denomination * 100.0).round();
+        if (denomination * 100.0 - cents).abs() > 1e-6 || cents >= i64::MAX as f64 {
+            return Err(format!("Denomination is not a whole number of cents: {}", denomination));
+        }
+        total_cents = (cents as i64)
+            .checked_mul(i64::from(quantity))
+            .and_then(|subtotal| total_cents.checked_add(subtotal))
+            .ok_or_else(|| "Cash count is too large".to_string())?;
+    }
+    Ok(total_cents as f64 / 100.0)
+}
+
+// Round a price to the nearest multiple of the display step (halves away from zero)
+fn round_to_step(price: f64, step: f64) -> f64 {
+    if !step.is_finite() || step <= 0.0 {
+        return price;
+    }
+    // Snap off float noise first so 10.15 / 0.1 counts as 101.5 steps, not 101.4999...
+    let steps = ((price / step) * 1e9).round() / 1e9;
+    let rounded = steps.round() * step;
+
+    // Trim the result to the step's own decimal places (0.005 -> 3)
+    let step_decimals = step
+        .to_string()
+        .split('.')
+        .nth(1)
+        .map_or(0, |fraction| fraction.len().min(10)) as i32;
+    let factor = 10f64.powi(step_decimals);
+    (rounded * factor).round() / factor
+}
+
+// Price as shown to the customer; the charged amount is left untouched
+#[tauri::command]
+fn round_display_price(state: tauri::State<AppState>, price: f64) -> Result<f64, String> {
+    let settings = state.settings.lock().map_err(|e| e.to_string())?;
+    Ok(round_to_step(price, settings.display_rounding))
+}
+
+// Selling price whose margin on price (not markup on cost) equals the target
+#[tauri::command]
+fn suggest_price(cost: f64, target_margin_percent: f64) -> Result<f64, String> {
+    if !cost.is_finite() || cost < 0.0 {
+        return Err(format!("Invalid cost: {}", cost));
+    }
+    if !(0.0..100.0).contains(&target_margin_percent) {
+        return Err(format!(
+            "Target margin must be at least 0% and below 100%, got {}%",
+            target_margin_percent
+        ));
+    }
+    let price = cost / (1.0 - target_margin_percent / 100.0);
+    if !price.is_finite() {
+        return Err(format!("Suggested price is out of range for cost {}", cost));
+    }
+    Ok(price)
+}
+
+// Weight request understood by NCI/Avery-style scales
+const SCALE_WEIGHT_REQUEST: &[u8] = b"W\r";
+
+// Parse a scale reply such as "ST,GS,+  1.234kg" or "001.50LB" into kilograms
+fn parse_scale_reply(reply: &str) -> Result<f64, String> {
+    let reply = reply.trim();
+    if reply.starts_with("US") {
+        return Err("Scale reading is not stable".to_string());
+    }
+    if reply.starts_with("OL") {
+        return Err("Scale is overloaded".to_string());
+    }
+
+    // The number starts at the first digit, or at a '.' directly before one (".5kg")
+    let bytes = reply.as_bytes();
+    let digits_start = (0..bytes.len())
+        .find(|&i| {
+            bytes[i].is_ascii_digit()
+                || (bytes[i] == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        })
+        .ok_or_else(|| format!("No weight in scale reply: {:?}", reply))?;
+    let negative = reply[..digits_start].trim_end().ends_with('-');
+    let rest = &reply[digits_start..];
+    let number_len = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(rest.len());
+    let value: f64 = rest[..number_len]
+        .parse()
+        .map_err(|e| format!("Invalid weight in scale reply {:?}: {}", reply, e))?;
+
+    // A missing or unknown unit is an error rather than a guess, since it prices the sale
+    let unit: String = rest[number_len..]
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let kilograms = match unit.as_str() {
+        "kg" => value,
+        "g" => value / 1000.0,
+        "lb" | "lbs" => value * 0.45359237,
+        "oz" => value * 0.028349523125,
+        "" => return Err(format!("No unit in scale reply: {:?}", reply)),
+        _ => return Err(format!("Unknown unit {:?} in scale reply: {:?}", unit, reply)),
+    };
+
+    Ok(if negative { -kilograms } else { kilograms })
+}
+
+fn read_scale_reply(port_name: &str) -> Result<String, String> {
+    use std::io::{Read, Write};
+
+    let mut port = serialport::new(port_name, 9600)
+        .timeout(Duration::from_secs(2))
+        .open()
+        .map_err(|e| format!("Failed to open scale port {}: {}", port_name, e))?;
+    port.write_all(SCALE_WEIGHT_REQUEST)
+        .map_err(|e| format!("Failed to send weight request: {}", e))?;
+
+    let mut reply = Vec::new();
+    let mut buf = [0u8; 64];
+    while reply.len() < 256 {
+        match port.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                reply.extend_from_slice(&buf[..n]);
+                // Stop at the end of the first line that carries a reading
+                if reply.iter().any(u8::is_ascii_digit) && matches!(reply.last(), Some(b'\r' | b'\n')) {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => break,
+            Err(e) => return Err(format!("Failed to read from scale: {}", e)),
+        }
+    }
+
+    if reply.is_empty() {
+        return Err("Scale did not respond".to_string());
+    }
+    Ok(String::from_utf8_lossy(&reply).into_owned())
+}
+
+#[tauri::command]
+async fn read_scale_weight(port: String) -> Result<f64, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let reply = read_scale_reply(&port)?;
+        parse_scale_reply(&reply)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 fn toggle_fullscreen(window: tauri::Window) -> Result<(), String> {
     let is_fullscreen = window.is_fullscreen().map_err(|e| e.to_string())?;
@@ -528,6 +1146,20 @@ const POPUP_HANDLER_SCRIPT: &str = r#"
             }
         }, true);
 
+        // Report user activity for the idle lock (throttled)
+        var lastActivityReport = 0;
+        function reportActivity() {
+            var now = Date.now();
+            if (now - lastActivityReport < 5000) return;
+            lastActivityReport = now;
+            if (window.__TAURI_INTERNALS__ && window.__TAURI_INTERNALS__.invoke) {
+                window.__TAURI_INTERNALS__.invoke('report_activity');
+            }
+        }
+        ['mousemove', 'mousedown', 'keydown', 'touchstart', 'wheel'].forEach(function(name) {
+            document.addEventListener(name, reportActivity, { capture: true, passive: true });
+        });
+
         console.log('[VOPECS] Handlers initialized');
     }
 
@@ -631,14 +1263,28 @@ fn main() {
             let settings_path = get_settings_path(app);
             let settings = load_settings(&settings_path);
 
+            // Remember how the last session ended, then flag this one as running
+            let session_path = get_session_path(app);
+            let previous_session = load_session(&session_path);
+            let _ = save_session(&session_path, &SessionState {
+                running: true,
+                last_clean_shutdown: previous_session.last_clean_shutdown,
+            });
+
             // Store state
             app.manage(AppState {
                 settings: Mutex::new(settings.clone()),
+                settings_audit_path: settings_path.with_file_name("settings_audit.jsonl"),
                 settings_path,
+                idle_timer: Mutex::new(IdleTimer::new(Instant::now())),
+                session_path,
+                previous_session,
+                last_reachable: Mutex::new(None),
             });
 
-            // Navigate to saved URL
+            // Apply saved zoom and navigate to saved URL
             if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_zoom(clamp_ui_zoom(settings.ui_zoom));
                 if let Ok(url) = settings.server_url.parse() {
                     let _ = window.navigate(url);
                 }
@@ -681,6 +1327,37 @@ fn main() {
                 }
             });
 
+            // Connectivity: re-check the server periodically so connectivity-changed
+            // reaches the UI without it polling is_server_reachable
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let timeout = Duration::from_millis(DEFAULT_CONNECTIVITY_TIMEOUT_MS);
+                loop {
+                    if let Err(e) = refresh_connectivity(&app_handle, timeout).await {
+                        eprintln!("Connectivity check failed: {}", e);
+                    }
+                    tokio::time::sleep(CONNECTIVITY_CHECK_INTERVAL).await;
+                }
+            });
+
+            // Idle lock: notify the main window once the terminal has been idle too long
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(Duration::from_secs(1));
+                let state: tauri::State<AppState> = app_handle.state();
+                let idle_lock_secs = match state.settings.lock() {
+                    Ok(settings) => settings.idle_lock_secs,
+                    Err(_) => continue,
+                };
+                let should_lock = match state.idle_timer.lock() {
+                    Ok(mut idle_timer) => idle_timer.check(Instant::now(), Duration::from_secs(idle_lock_secs)),
+                    Err(_) => false,
+                };
+                if should_lock {
+                    let _ = app_handle.emit_to("main", "lock-screen", ());
+                }
+            });
+
             // Handle menu events
             app.on_menu_event(move |app, event| {
                 match event.id().as_ref() {
@@ -749,7 +1426,17 @@ fn main() {
             save_settings,
             get_server_url,
             set_server_url,
+            ping_server,
+            is_server_reachable,
+            get_settings_audit,
+            set_ui_zoom,
             toggle_fullscreen,
+            report_activity,
+            get_shutdown_status,
+            compute_cash_count,
+            suggest_price,
+            round_display_price,
+            read_scale_weight,
             open_settings,
             open_main_devtools,
             open_popup_window,
@@ -758,6 +1445,369 @@ fn main() {
             open_in_browser,
             open_print_window,
         ])
-        .run(tauri::generate_context!())
-        .expect("Error while running VOPECS POS");
+        .build(tauri::generate_context!())
+        .expect("Error while running VOPECS POS")
+        .run(|app, event| {
+            // Fires for the Quit menu item and when the last window closes
+            if let tauri::RunEvent::Exit = event {
+                mark_clean_shutdown(app);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vopecs-pos-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn server_url_change_writes_one_audit_entry() {
+        let old = AppSettings::default();
+        let new = AppSettings { server_url: "https://pos.example.com/".to_string(), ..old.clone() };
+
+        let entries = diff_settings(&old, &new);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].field, "server_url");
+        assert_eq!(entries[0].old_value, serde_json::json!("https://po.megacaresa.com/"));
+        assert_eq!(entries[0].new_value, serde_json::json!("https://pos.example.com/"));
+    }
+
+    #[test]
+    fn sensitive_settings_are_redacted_in_audit() {
+        let old = serde_json::json!({ "manager_pin": "1234", "fullscreen": false });
+        let new = serde_json::json!({ "manager_pin": "9876", "fullscreen": false });
+
+        let entries = diff_settings_values(&old, &new, 0);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].field, "manager_pin");
+        assert_eq!(entries[0].old_value, serde_json::json!("[redacted]"));
+        assert_eq!(entries[0].new_value, serde_json::json!("[redacted]"));
+    }
+
+    #[test]
+    fn running_session_reads_as_unclean() {
+        let path = test_dir("session-running").join("session.json");
+        save_session(&path, &SessionState { running: true, last_clean_shutdown: Some(100) }).unwrap();
+
+        let status = shutdown_status(&load_session(&path));
+        assert!(!status.previous_clean);
+        assert_eq!(status.last_clean_shutdown, Some(100));
+    }
+
+    #[test]
+    fn clean_shutdown_reads_as_clean_with_timestamp() {
+        let path = test_dir("session-clean").join("session.json");
+        save_session(&path, &SessionState { running: true, last_clean_shutdown: None }).unwrap();
+        save_clean_shutdown(&path, 1_700_000_000).unwrap();
+
+        let status = shutdown_status(&load_session(&path));
+        assert!(status.previous_clean);
+        assert_eq!(status.last_clean_shutdown, Some(1_700_000_000));
+    }
+
+    #[test]
+    fn missing_session_file_reads_as_clean() {
+        let path = test_dir("session-missing").join("session.json");
+
+        let status = shutdown_status(&load_session(&path));
+        assert!(status.previous_clean);
+        assert_eq!(status.last_clean_shutdown, None);
+    }
+
+    #[test]
+    fn cash_count_sums_mixed_denominations() {
+        let counts = vec![(500.0, 1), (100.0, 3), (50.0, 2), (5.0, 4), (1.0, 7), (0.5, 3), (0.05, 7)];
+        assert_eq!(compute_cash_count(counts), Ok(928.85));
+    }
+
+    #[test]
+    fn cash_count_rejects_invalid_denominations() {
+        assert!(compute_cash_count(vec![(10.0, 1), (-5.0, 2)]).is_err());
+        assert!(compute_cash_count(vec![(0.0, 1)]).is_err());
+        assert!(compute_cash_count(vec![(f64::NAN, 1)]).is_err());
+        assert!(compute_cash_count(vec![(f64::INFINITY, 1)]).is_err());
+    }
+
+    #[test]
+    fn cash_count_rejects_sub_cent_denominations() {
+        assert!(compute_cash_count(vec![(0.005, 3)]).is_err());
+        assert!(compute_cash_count(vec![(0.001, 10)]).is_err());
+        assert_eq!(compute_cash_count(vec![(0.25, 4)]), Ok(1.0));
+    }
+
+    #[test]
+    fn cash_count_rejects_overflow() {
+        assert!(compute_cash_count(vec![(1e17, 1000)]).is_err());
+        assert!(compute_cash_count(vec![(1e15, u32::MAX)]).is_err());
+        assert!(compute_cash_count(vec![(1e16, 4), (1e16, 4), (1e16, 4)]).is_err());
+    }
+
+    #[test]
+    fn display_price_rounds_to_step() {
+        assert_eq!(round_to_step(10.12, 0.05), 10.1);
+        assert_eq!(round_to_step(10.13, 0.05), 10.15);
+        assert_eq!(round_to_step(10.14, 0.10), 10.1);
+        assert_eq!(round_to_step(10.15, 0.10), 10.2);
+        assert_eq!(round_to_step(9.49, 1.00), 9.0);
+        assert_eq!(round_to_step(9.5, 1.00), 10.0);
+        assert_eq!(round_to_step(1.2337, 0.005), 1.235);
+    }
+
+    #[test]
+    fn display_price_rounding_disabled_by_zero_step() {
+        assert_eq!(round_to_step(3.33, 0.0), 3.33);
+        assert_eq!(round_to_step(3.33, -0.05), 3.33);
+    }
+
+    #[test]
+    fn display_price_rounds_negative_prices() {
+        assert_eq!(round_to_step(-10.13, 0.05), -10.15);
+        assert_eq!(round_to_step(-10.12, 0.05), -10.1);
+    }
+
+    #[test]
+    fn ui_zoom_is_clamped() {
+        assert_eq!(clamp_ui_zoom(0.1), 0.5);
+        assert_eq!(clamp_ui_zoom(5.0), 3.0);
+        assert_eq!(clamp_ui_zoom(f64::NAN), 1.0);
+        assert_eq!(clamp_ui_zoom(1.25), 1.25);
+    }
+
+    #[test]
+    fn ui_zoom_survives_save_and_load() {
+        let path = test_dir("ui-zoom").join("settings.json");
+        let settings = AppSettings { ui_zoom: clamp_ui_zoom(1.25), ..AppSettings::default() };
+        save_settings_to_file(&path, &settings).unwrap();
+
+        assert_eq!(load_settings(&path).ui_zoom, 1.25);
+    }
+
+    #[test]
+    fn legacy_settings_keep_server_url_and_default_new_fields() {
+        let path = test_dir("settings-legacy").join("settings.json");
+        fs::write(&path, r#"{"server_url":"http://old.example/","window_width":1280,"window_height":800,"fullscreen":true}"#).unwrap();
+
+        let settings = load_settings(&path);
+        assert_eq!(settings.server_url, "http://old.example/");
+        assert_eq!(settings.window_width, 1280);
+        assert!(settings.fullscreen);
+        assert_eq!(settings.idle_lock_secs, 0);
+        assert_eq!(settings.ui_zoom, 1.0);
+
+        // Missing fields are written back
+        let rewritten: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten["server_url"], "http://old.example/");
+        assert_eq!(rewritten["ui_zoom"], 1.0);
+    }
+
+    #[test]
+    fn mistyped_setting_falls_back_without_losing_others() {
+        let path = test_dir("settings-mistyped").join("settings.json");
+        let original = r#"{"server_url":"http://old.example/","window_width":"wide","window_height":700,"fullscreen":false,"idle_lock_secs":0,"display_rounding":0.0,"ui_zoom":1.0,"future_field":true}"#;
+        fs::write(&path, original).unwrap();
+
+        let settings = load_settings(&path);
+        assert_eq!(settings.server_url, "http://old.example/");
+        assert_eq!(settings.window_width, AppSettings::default().window_width);
+        assert_eq!(settings.window_height, 700);
+
+        // Nothing was missing, so the file (including the bad value and unknown key) is untouched
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn unknown_settings_keys_survive_rewrite() {
+        let path = test_dir("settings-unknown").join("settings.json");
+        fs::write(&path, r#"{"server_url":"http://old.example/","future_field":"keep me"}"#).unwrap();
+
+        load_settings(&path);
+        let rewritten: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten["future_field"], "keep me");
+        assert_eq!(rewritten["window_width"], 1400);
+    }
+
+    #[test]
+    fn atomic_write_replaces_whole_file() {
+        let dir = test_dir("atomic-write");
+        let path = dir.join("settings.json");
+        let old = AppSettings::default();
+        let new = AppSettings { server_url: "https://pos.example.com/".to_string(), ..AppSettings::default() };
+
+        let read_back = || -> AppSettings { serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap() };
+
+        save_settings_to_file(&path, &old).unwrap();
+        assert_eq!(read_back().server_url, old.server_url);
+        save_settings_to_file(&path, &new).unwrap();
+        assert_eq!(read_back().server_url, new.server_url);
+        assert!(!dir.join("settings.json.tmp").exists());
+    }
+
+    #[test]
+    fn atomic_write_leaves_target_and_no_temp_file_on_failure() {
+        let dir = test_dir("atomic-write-fail");
+        // A non-empty directory at the target path makes the final rename fail
+        let path = dir.join("settings.json");
+        fs::create_dir_all(path.join("occupied")).unwrap();
+
+        assert!(write_file_atomic(&path, "{}").is_err());
+        assert!(path.join("occupied").is_dir());
+        assert!(!dir.join("settings.json.tmp").exists());
+    }
+
+    #[test]
+    fn scale_reply_parses_supported_formats() {
+        assert_eq!(parse_scale_reply("ST,GS,+  1.234kg\r\n"), Ok(1.234));
+        assert_eq!(parse_scale_reply("\n001.50LB\r\n00\r"), Ok(1.5 * 0.45359237));
+        assert_eq!(parse_scale_reply("  250 g"), Ok(0.25));
+        assert_eq!(parse_scale_reply("ST,NT,-  0.100kg"), Ok(-0.1));
+        assert_eq!(parse_scale_reply(".5kg"), Ok(0.5));
+        assert_eq!(parse_scale_reply("  .500 kg"), Ok(0.5));
+        assert_eq!(parse_scale_reply("ST,GS,- .250kg"), Ok(-0.25));
+    }
+
+    #[test]
+    fn scale_reply_rejects_unusable_readings() {
+        assert!(parse_scale_reply("US,GS,+  1.234kg").is_err());
+        assert!(parse_scale_reply("OL,GS,+9999.99kg").is_err());
+        assert!(parse_scale_reply("ERR").is_err());
+        assert!(parse_scale_reply("  2.5").is_err());
+        assert!(parse_scale_reply("ST,GS,+  2.5xy").is_err());
+    }
+
+    #[test]
+    fn suggested_price_round_trips_to_target_margin() {
+        for &(cost, margin) in &[(60.0, 40.0), (12.5, 25.0), (3.0, 0.0), (99.99, 72.5)] {
+            let price = suggest_price(cost, margin).unwrap();
+            let achieved = (price - cost) / price * 100.0;
+            assert!((achieved - margin).abs() < 1e-9, "cost {} margin {} gave {}", cost, margin, achieved);
+        }
+        assert_eq!(suggest_price(60.0, 40.0), Ok(100.0));
+    }
+
+    #[test]
+    fn suggest_price_rejects_invalid_input() {
+        assert!(suggest_price(10.0, 100.0).is_err());
+        assert!(suggest_price(10.0, -1.0).is_err());
+        assert!(suggest_price(10.0, f64::NAN).is_err());
+        assert!(suggest_price(-10.0, 30.0).is_err());
+        assert!(suggest_price(1e308, 99.99).is_err());
+    }
+
+    #[test]
+    fn server_url_accepts_http_and_normalizes() {
+        assert_eq!(normalize_server_url("https://host").as_deref(), Ok("https://host/"));
+        assert_eq!(normalize_server_url("  https://host/pos  ").as_deref(), Ok("https://host/pos/"));
+        assert_eq!(normalize_server_url("http://10.0.0.5:8000/pos/").as_deref(), Ok("http://10.0.0.5:8000/pos/"));
+        assert_eq!(
+            normalize_server_url("https://host/pos/index.html").as_deref(),
+            Ok("https://host/pos/index.html")
+        );
+    }
+
+    #[test]
+    fn server_url_rejects_bad_scheme_or_host() {
+        assert!(normalize_server_url("htp://x").is_err());
+        assert!(normalize_server_url("ftp://host").is_err());
+        assert!(normalize_server_url("https://").is_err());
+        assert!(normalize_server_url("not a url").is_err());
+    }
+
+    #[test]
+    fn connectivity_timeout_defaults_and_rejects_too_short() {
+        assert_eq!(connectivity_timeout(None), Ok(Duration::from_secs(3)));
+        assert_eq!(connectivity_timeout(Some(1000)), Ok(Duration::from_secs(1)));
+        assert!(connectivity_timeout(Some(0)).is_err());
+        assert!(connectivity_timeout(Some(MIN_CONNECTIVITY_TIMEOUT_MS - 1)).is_err());
+    }
+
+    #[test]
+    fn connectivity_change_reported_only_on_flip() {
+        let last_reachable = Mutex::new(None);
+        assert_eq!(update_last_reachable(&last_reachable, true), Ok(true));
+        assert_eq!(update_last_reachable(&last_reachable, true), Ok(false));
+        assert_eq!(update_last_reachable(&last_reachable, false), Ok(true));
+        assert_eq!(update_last_reachable(&last_reachable, false), Ok(false));
+    }
+
+    #[tokio::test]
+    async fn connectivity_reports_reachable_server() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        });
+
+        let connectivity = check_connectivity(&url, Duration::from_secs(3)).await;
+        assert!(connectivity.reachable);
+        assert_eq!(connectivity.status, Some(204));
+        assert!(connectivity.latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn connectivity_times_out_on_silent_server() {
+        // Connections are queued by the OS but never answered
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let started = Instant::now();
+        let connectivity = check_connectivity(&url, Duration::from_millis(300)).await;
+        assert!(!connectivity.reachable);
+        assert_eq!(connectivity.status, None);
+        assert_eq!(connectivity.latency_ms, None);
+        assert!(started.elapsed() < Duration::from_secs(3));
+        drop(listener);
+    }
+
+    #[test]
+    fn idle_timer_fires_at_threshold() {
+        let start = Instant::now();
+        let mut timer = IdleTimer::new(start);
+        let threshold = Duration::from_secs(60);
+
+        assert!(!timer.check(start + Duration::from_secs(59), threshold));
+        assert!(timer.check(start + threshold, threshold));
+    }
+
+    #[test]
+    fn idle_timer_fires_once_per_idle_period() {
+        let start = Instant::now();
+        let mut timer = IdleTimer::new(start);
+        let threshold = Duration::from_secs(60);
+
+        assert!(timer.check(start + threshold, threshold));
+        assert!(!timer.check(start + Duration::from_secs(61), threshold));
+        assert!(!timer.check(start + Duration::from_secs(600), threshold));
+    }
+
+    #[test]
+    fn idle_timer_rearms_after_reset() {
+        let start = Instant::now();
+        let mut timer = IdleTimer::new(start);
+        let threshold = Duration::from_secs(60);
+        assert!(timer.check(start + threshold, threshold));
+
+        let activity = start + Duration::from_secs(100);
+        timer.reset(activity);
+        assert!(!timer.check(activity + Duration::from_secs(59), threshold));
+        assert!(timer.check(activity + threshold, threshold));
+    }
+
+    #[test]
+    fn idle_timer_never_fires_when_disabled() {
+        let start = Instant::now();
+        let mut timer = IdleTimer::new(start);
+
+        assert!(!timer.check(start + Duration::from_secs(86_400), Duration::ZERO));
+    }
 }